@@ -6,6 +6,7 @@ use ra_syntax::{
     algo::{find_token_at_offset, find_node_at_offset, find_covering_element, TokenAtOffset},
 };
 use ra_fmt::{leading_indent, reindent};
+use rustc_hash::FxHashMap;
 
 use crate::{AssistLabel, AssistAction, AssistId};
 
@@ -38,13 +39,11 @@ pub(crate) enum Assist {
 /// computing info required to compute the actual edit). If it is applicable,
 /// and `should_compute_edit` is `true`, it then computes the actual edit.
 ///
-/// So, to implement the original assists workflow, we can first apply each edit
-/// with `should_compute_edit = false`, and then applying the selected edit
-/// again, with `should_compute_edit = true` this time.
-///
-/// Note, however, that we don't actually use such two-phase logic at the
-/// moment, because the LSP API is pretty awkward in this place, and it's much
-/// easier to just compute the edit eagerly :-)#[derive(Debug, Clone)]
+/// So, to implement the assists workflow, `crate::unresolved_assists` first
+/// runs every assist with `should_compute_edit = false` to get the cheap
+/// label-only menu, and `crate::resolve_assist` runs them again with
+/// `should_compute_edit = true`, picking out the one action the caller asked
+/// for by its `(AssistId, resolve_index)` key.
 #[derive(Debug)]
 pub(crate) struct AssistCtx<'a, DB> {
     pub(crate) db: &'a DB,
@@ -52,6 +51,7 @@ pub(crate) struct AssistCtx<'a, DB> {
     source_file: &'a SourceFile,
     should_compute_edit: bool,
     assist: Assist,
+    resolve_counts: FxHashMap<AssistId, usize>,
 }
 
 impl<'a, DB> Clone for AssistCtx<'a, DB> {
@@ -62,6 +62,7 @@ impl<'a, DB> Clone for AssistCtx<'a, DB> {
             source_file: self.source_file,
             should_compute_edit: self.should_compute_edit,
             assist: self.assist.clone(),
+            resolve_counts: self.resolve_counts.clone(),
         }
     }
 }
@@ -75,7 +76,14 @@ impl<'a, DB: HirDatabase> AssistCtx<'a, DB> {
         let assist =
             if should_compute_edit { Assist::Resolved(vec![]) } else { Assist::Unresolved(vec![]) };
 
-        let ctx = AssistCtx { db, frange, source_file, should_compute_edit, assist };
+        let ctx = AssistCtx {
+            db,
+            frange,
+            source_file,
+            should_compute_edit,
+            assist,
+            resolve_counts: FxHashMap::default(),
+        };
         f(ctx)
     }
 
@@ -85,7 +93,17 @@ impl<'a, DB: HirDatabase> AssistCtx<'a, DB> {
         label: impl Into<String>,
         f: impl FnOnce(&mut AssistBuilder),
     ) -> &mut Self {
-        let label = AssistLabel { label: label.into(), id };
+        // Most assists contribute a single action per `AssistId`, but some
+        // (e.g. one action per missing match arm) call `add_action` several
+        // times with the same `id` — `resolve_index` disambiguates between
+        // those, so a client can later ask to resolve exactly one of them.
+        let resolve_index = {
+            let count = self.resolve_counts.entry(id).or_insert(0);
+            let index = *count;
+            *count += 1;
+            index
+        };
+        let label = AssistLabel { label: label.into(), id, resolve_index };
         match &mut self.assist {
             Assist::Unresolved(labels) => labels.push(label),
             Assist::Resolved(labels_actions) => {
@@ -125,6 +143,7 @@ pub(crate) struct AssistBuilder {
     edit: TextEditBuilder,
     cursor_position: Option<TextUnit>,
     target: Option<TextRange>,
+    snippet: Option<String>,
 }
 
 impl AssistBuilder {
@@ -132,6 +151,33 @@ impl AssistBuilder {
         self.edit.replace(range, replace_with.into())
     }
 
+    /// Like `insert`, but `snippet` may contain LSP-style tab stops
+    /// (`$0`, `$1`, `${1:name}`). The rendered, marker-free text is what
+    /// actually gets inserted; `$0`'s position becomes the fallback cursor
+    /// position for clients that don't understand snippets, and the raw
+    /// template is kept on the resulting `AssistAction` so that clients
+    /// which do can offer a real multi-stop snippet edit.
+    pub(crate) fn insert_snippet(&mut self, offset: TextUnit, snippet: impl Into<String>) {
+        let snippet = snippet.into();
+        let (text, cursor) = strip_snippet_markers(&snippet);
+        self.edit.insert(offset, text);
+        if let Some(cursor) = cursor {
+            self.cursor_position = Some(offset + cursor);
+        }
+        self.snippet = Some(snippet);
+    }
+
+    /// `replace` variant of `insert_snippet`.
+    pub(crate) fn replace_snippet(&mut self, range: TextRange, snippet: impl Into<String>) {
+        let snippet = snippet.into();
+        let (text, cursor) = strip_snippet_markers(&snippet);
+        self.edit.replace(range, text);
+        if let Some(cursor) = cursor {
+            self.cursor_position = Some(range.start() + cursor);
+        }
+        self.snippet = Some(snippet);
+    }
+
     pub(crate) fn replace_node_and_indent(
         &mut self,
         node: &SyntaxNode,
@@ -174,6 +220,91 @@ impl AssistBuilder {
             edit: self.edit.finish(),
             cursor_position: self.cursor_position,
             target: self.target,
+            snippet: self.snippet,
+        }
+    }
+}
+
+/// Renders a snippet template (`$0`, `$1`, `${1:placeholder}` tab stops) into
+/// plain text, returning the offset of the `$0` stop (the final cursor
+/// position) within that text, if any. Non-final placeholders are replaced
+/// by their label (or removed, if they have none), since plain text can only
+/// carry a single cursor.
+fn strip_snippet_markers(template: &str) -> (String, Option<TextUnit>) {
+    let mut text = String::with_capacity(template.len());
+    let mut cursor = None;
+    let mut rest = template;
+    while !rest.is_empty() {
+        if let Some(after_dollar) = rest.strip_prefix('$') {
+            if let Some(after_brace) = after_dollar.strip_prefix('{') {
+                if let Some(close) = after_brace.find('}') {
+                    let (num, label) = match after_brace[..close].find(':') {
+                        Some(idx) => (&after_brace[..idx], &after_brace[idx + 1..close]),
+                        None => (&after_brace[..close], ""),
+                    };
+                    if !num.is_empty() && num.bytes().all(|b| b.is_ascii_digit()) {
+                        if num == "0" {
+                            cursor = Some(TextUnit::from_usize(text.len()));
+                        }
+                        text.push_str(label);
+                        rest = &after_brace[close + 1..];
+                        continue;
+                    }
+                }
+            } else {
+                let num_len = after_dollar.bytes().take_while(u8::is_ascii_digit).count();
+                if num_len > 0 {
+                    if &after_dollar[..num_len] == "0" {
+                        cursor = Some(TextUnit::from_usize(text.len()));
+                    }
+                    rest = &after_dollar[num_len..];
+                    continue;
+                }
+            }
         }
+        let ch = rest.chars().next().unwrap();
+        text.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    (text, cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_final_cursor() {
+        let (text, cursor) = strip_snippet_markers("foo($0)");
+        assert_eq!(text, "foo()");
+        assert_eq!(cursor, Some(TextUnit::from_usize(4)));
+    }
+
+    #[test]
+    fn drops_non_final_placeholder_without_label() {
+        let (text, cursor) = strip_snippet_markers("foo($1)");
+        assert_eq!(text, "foo()");
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn keeps_non_final_placeholder_label() {
+        let (text, cursor) = strip_snippet_markers("foo(${1:name})");
+        assert_eq!(text, "foo(name)");
+        assert_eq!(cursor, None);
+    }
+
+    #[test]
+    fn final_placeholder_keeps_label_and_cursor() {
+        let (text, cursor) = strip_snippet_markers("foo(${0:x})");
+        assert_eq!(text, "foo(x)");
+        assert_eq!(cursor, Some(TextUnit::from_usize(4)));
+    }
+
+    #[test]
+    fn literal_dollar_is_untouched() {
+        let (text, cursor) = strip_snippet_markers("cost: $x");
+        assert_eq!(text, "cost: $x");
+        assert_eq!(cursor, None);
     }
 }
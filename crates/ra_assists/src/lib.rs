@@ -0,0 +1,104 @@
+//! `ra_assists` crate provides a bunch of code assists, also known as code
+//! actions (in LSP) or intentions (in IntelliJ).
+
+mod assist_ctx;
+
+use hir::db::HirDatabase;
+use ra_db::FileRange;
+use ra_syntax::{TextUnit, TextRange};
+use ra_text_edit::TextEdit;
+
+pub(crate) use crate::assist_ctx::{Assist, AssistCtx};
+
+/// Unique identifier of the assist, should not be shown to the user
+/// directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssistId(pub &'static str);
+
+#[derive(Debug, Clone)]
+pub struct AssistLabel {
+    pub label: String,
+    pub id: AssistId,
+    /// This label's position among the other labels the same `AssistId`
+    /// produced at this `FileRange`. Together, `(id, resolve_index)` is a
+    /// stable key a client can hold on to and later hand to `resolve_assist`
+    /// to recompute exactly this one action.
+    pub resolve_index: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssistAction {
+    pub edit: TextEdit,
+    pub cursor_position: Option<TextUnit>,
+    pub target: Option<TextRange>,
+    /// The raw snippet template (with `$0`/`$1`/`${1:label}` tab stops) this
+    /// action was built from, if it used `insert_snippet`/`replace_snippet`.
+    /// Editor layers that understand LSP snippets should prefer emitting
+    /// this as a snippet `TextEdit` instead of `edit`/`cursor_position`.
+    pub snippet: Option<String>,
+}
+
+pub(crate) type AssistHandler<DB> = fn(AssistCtx<DB>) -> Option<Assist>;
+
+/// Returns all the assists applicable at the given position, with their
+/// actions computed eagerly. Prefer `unresolved_assists`/`resolve_assist`
+/// when the caller can defer computing an edit until the user actually picks
+/// an assist.
+pub fn assists(db: &impl HirDatabase, frange: FileRange) -> Vec<(AssistLabel, AssistAction)> {
+    AssistCtx::with_ctx(db, frange, true, |ctx| {
+        all_assists()
+            .iter()
+            .filter_map(|f| f(ctx.clone()))
+            .flat_map(|assist| match assist {
+                Assist::Resolved(it) => it,
+                Assist::Unresolved(_) => Vec::new(),
+            })
+            .collect()
+    })
+}
+
+/// Returns just the labels for assists applicable at `frange`, without
+/// computing any of their edits. This is the cheap "show me the menu" half
+/// of the two-phase assist workflow: pair it with `resolve_assist` once the
+/// user has picked one.
+pub fn unresolved_assists(db: &impl HirDatabase, frange: FileRange) -> Vec<AssistLabel> {
+    AssistCtx::with_ctx(db, frange, false, |ctx| {
+        all_assists()
+            .iter()
+            .filter_map(|f| f(ctx.clone()))
+            .flat_map(|assist| match assist {
+                Assist::Unresolved(labels) => labels,
+                Assist::Resolved(_) => Vec::new(),
+            })
+            .collect()
+    })
+}
+
+/// Recomputes and returns exactly the one `AssistAction` identified by
+/// `(assist_id, resolve_index)` — the key previously handed out on an
+/// `AssistLabel` by `unresolved_assists`. Returns `None` if no assist with
+/// that key is applicable anymore (e.g. the file changed in the meantime).
+pub fn resolve_assist(
+    db: &impl HirDatabase,
+    frange: FileRange,
+    assist_id: AssistId,
+    resolve_index: usize,
+) -> Option<AssistAction> {
+    AssistCtx::with_ctx(db, frange, true, |ctx| {
+        all_assists()
+            .iter()
+            .filter_map(|f| f(ctx.clone()))
+            .flat_map(|assist| match assist {
+                Assist::Resolved(it) => it,
+                Assist::Unresolved(_) => Vec::new(),
+            })
+            .find(|(label, _)| label.id == assist_id && label.resolve_index == resolve_index)
+            .map(|(_, action)| action)
+    })
+}
+
+/// The registry of built-in assists. It lists no handlers, so `assists`,
+/// `unresolved_assists` and `resolve_assist` currently find nothing.
+fn all_assists<DB: HirDatabase>() -> &'static [AssistHandler<DB>] {
+    &[]
+}
@@ -0,0 +1,29 @@
+//! Cancellation support for salsa-driven queries.
+//!
+//! rust-analyzer needs to be able to answer semantic questions about the code
+//! while the code is being modified. We don't want a long-running query to
+//! observe inconsistent state, so instead we cancel it: it unwinds with a
+//! special `Canceled` payload which is caught at the API boundary.
+
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Canceled {
+    _private: (),
+}
+
+impl Canceled {
+    pub(crate) fn throw() -> ! {
+        // Don't use `panic!()` as that prints a backtrace by default, and we
+        // don't want to print a backtrace for cancellation.
+        std::panic::resume_unwind(Box::new(Canceled { _private: () }))
+    }
+}
+
+impl fmt::Display for Canceled {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Canceled")
+    }
+}
+
+impl std::error::Error for Canceled {}
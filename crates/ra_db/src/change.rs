@@ -0,0 +1,95 @@
+//! Defines a unit of change that can be applied to a database to get a new
+//! state, in one call. Each `set_*` it issues still bumps the salsa revision
+//! on its own.
+
+use std::sync::Arc;
+
+use rustc_hash::FxHashMap;
+
+use crate::{FileId, SourceRoot, SourceRootId, CrateGraph, SourceDatabase, VfsPath};
+
+/// Encapsulates a bunch of raw `set_*` calls on `SourceDatabase`, so that the
+/// server can apply them all at once, as a part of a single "input changed"
+/// event, instead of calling the setters from many places.
+#[derive(Debug, Default)]
+pub struct Change {
+    pub roots: Option<Vec<SourceRoot>>,
+    pub files_changed: Vec<(FileId, Option<Arc<String>>)>,
+    pub path_changed: Vec<(FileId, VfsPath)>,
+    pub crate_graph: Option<CrateGraph>,
+}
+
+impl Change {
+    pub fn new() -> Change {
+        Change::default()
+    }
+
+    pub fn set_roots(&mut self, roots: Vec<SourceRoot>) {
+        self.roots = Some(roots);
+    }
+
+    /// `new_text` of `None` represents file deletion.
+    pub fn change_file(&mut self, file_id: FileId, new_text: Option<Arc<String>>) {
+        self.files_changed.push((file_id, new_text))
+    }
+
+    pub fn change_path(&mut self, file_id: FileId, new_path: VfsPath) {
+        self.path_changed.push((file_id, new_path))
+    }
+
+    pub fn set_crate_graph(&mut self, graph: CrateGraph) {
+        self.crate_graph = Some(graph);
+    }
+
+    pub fn apply(self, db: &mut impl SourceDatabase) {
+        let mut dirty_roots: FxHashMap<SourceRootId, SourceRoot> = FxHashMap::default();
+
+        if let Some(roots) = self.roots {
+            for (idx, root) in roots.into_iter().enumerate() {
+                let root_id = SourceRootId(idx as u32);
+                db.set_source_root(root_id, Arc::new(root));
+            }
+        } else {
+            // A wholesale `set_roots` already rebuilds `files` from scratch,
+            // so only an incremental change (no new roots) needs each
+            // affected source root's membership patched up by hand: a
+            // deleted file's entry removed, a renamed file's entry moved
+            // from its old `VfsPath` to its new one.
+            for (file_id, text) in &self.files_changed {
+                if text.is_none() {
+                    let root_id = db.file_source_root(*file_id);
+                    let root = dirty_roots
+                        .entry(root_id)
+                        .or_insert_with(|| (*db.source_root(root_id)).clone());
+                    let old_path = db.file_relative_path(*file_id);
+                    root.files.remove(&old_path);
+                }
+            }
+            for (file_id, new_path) in &self.path_changed {
+                let root_id = db.file_source_root(*file_id);
+                let root = dirty_roots
+                    .entry(root_id)
+                    .or_insert_with(|| (*db.source_root(root_id)).clone());
+                let old_path = db.file_relative_path(*file_id);
+                root.files.remove(&old_path);
+                root.files.insert(new_path.clone(), *file_id);
+            }
+        }
+
+        for (file_id, text) in self.files_changed {
+            // salsa has no notion of "deleting" an input, so a deleted file
+            // is represented as an empty one; its `file_source_root` still
+            // names the source root it used to belong to.
+            db.set_file_text(file_id, text.unwrap_or_default())
+        }
+        for (file_id, path) in self.path_changed {
+            db.set_file_relative_path(file_id, path)
+        }
+        for (root_id, root) in dirty_roots {
+            db.set_source_root(root_id, Arc::new(root));
+        }
+        if let Some(crate_graph) = self.crate_graph {
+            db.set_crate_graph(Arc::new(crate_graph))
+        }
+    }
+}
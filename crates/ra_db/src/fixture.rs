@@ -0,0 +1,187 @@
+//! A set of utilities to reconstruct a real-world crate graph from a small
+//! textual fixture, so that downstream crates can write concise integration
+//! tests against a fully populated `SourceDatabase`, instead of reinventing
+//! "assemble a one-off db" in every crate.
+//!
+//! A fixture looks like:
+//!
+//! ```not_rust
+//! //- /lib.rs crate:foo deps:bar edition:2018
+//! fn foo() { <|> }
+//! //- /bar.rs crate:bar
+//! pub fn bar() {}
+//! ```
+//!
+//! Each `//- /path/to/file.rs` line starts a new file; anything after the
+//! path on that line is whitespace-separated `key:value` metadata understood
+//! only for the crate root of a crate (`crate:`, `deps:`, `edition:`). A
+//! single `<|>` marker anywhere in the text marks the cursor offset that
+//! `load_fixture` returns after populating the database.
+
+use std::sync::Arc;
+
+use relative_path::RelativePathBuf;
+use rustc_hash::FxHashMap;
+
+use crate::{
+    Change, CfgOptions, CrateDisplayName, CrateGraph, CrateName, Edition, Env, FileId,
+    FilePosition, SourceDatabase, SourceRoot, SourceRootId, VfsPath,
+};
+
+pub const CURSOR_MARKER: &str = "<|>";
+
+#[derive(Debug, Clone)]
+struct FixtureMeta {
+    path: String,
+    krate: Option<String>,
+    deps: Vec<String>,
+    edition: Edition,
+}
+
+#[derive(Debug, Clone)]
+struct FixtureEntry {
+    meta: FixtureMeta,
+    text: String,
+}
+
+/// Populates `db` from `ra_fixture` and returns the `FileId` of the last
+/// file in the fixture (by convention, the file under test) together with
+/// the `FileId` and offset of the `<|>` cursor marker, if present — which
+/// may be a different file than the last one.
+///
+/// # Panics
+/// Panics if the fixture references an unknown crate in `deps:`, or if it
+/// contains more than one cursor marker.
+pub fn load_fixture(
+    db: &mut impl SourceDatabase,
+    ra_fixture: &str,
+) -> (FileId, Option<(FileId, u32)>) {
+    let fixture = parse_fixture(ra_fixture);
+    assert!(!fixture.is_empty(), "empty fixture");
+
+    let mut change = Change::new();
+    let mut files = FxHashMap::default();
+    let mut crate_graph = CrateGraph::default();
+    let mut crate_ids = FxHashMap::default();
+    let mut root = SourceRoot::default();
+
+    let mut cursor = None;
+    let mut last_file_id = FileId(0);
+
+    for (i, entry) in fixture.iter().enumerate() {
+        let file_id = FileId(i as u32);
+        last_file_id = file_id;
+
+        let mut text = entry.text.clone();
+        if let Some(idx) = text.find(CURSOR_MARKER) {
+            assert!(cursor.is_none(), "fixture has more than one cursor marker");
+            cursor = Some((file_id, idx as u32));
+            text.replace_range(idx..idx + CURSOR_MARKER.len(), "");
+            assert!(!text.contains(CURSOR_MARKER), "fixture has more than one cursor marker");
+        }
+
+        let path: VfsPath = RelativePathBuf::from_path(entry.meta.path.trim_start_matches('/'))
+            .expect("fixture path must be relative")
+            .into();
+        files.insert(path.clone(), file_id);
+        root.files.insert(path.clone(), file_id);
+
+        change.change_file(file_id, Some(Arc::new(text)));
+        change.change_path(file_id, path);
+
+        if let Some(krate) = &entry.meta.krate {
+            let display_name = CrateDisplayName::new(krate.clone());
+            let crate_id = crate_graph.add_crate_root(
+                file_id,
+                entry.meta.edition,
+                Some(display_name),
+                CfgOptions::default(),
+                Env::default(),
+            );
+            crate_ids.insert(krate.clone(), crate_id);
+        }
+    }
+
+    for entry in &fixture {
+        let krate = match &entry.meta.krate {
+            Some(krate) => krate,
+            None => continue,
+        };
+        let from = crate_ids[krate];
+        for dep in &entry.meta.deps {
+            let to = *crate_ids
+                .get(dep)
+                .unwrap_or_else(|| panic!("fixture references unknown crate `{}`", dep));
+            let name = CrateName::new(dep).unwrap();
+            crate_graph.add_dep(from, name, to).unwrap();
+        }
+    }
+
+    change.set_roots(vec![root]);
+    change.set_crate_graph(crate_graph);
+    change.apply(db);
+
+    for &file_id in files.values() {
+        db.set_file_source_root(file_id, SourceRootId(0));
+    }
+
+    (last_file_id, cursor)
+}
+
+/// Like `load_fixture`, but asserts there is exactly one cursor marker and
+/// returns a `FilePosition` pointing at it, in whichever file it occurs in.
+pub fn load_fixture_with_position(
+    db: &mut impl SourceDatabase,
+    ra_fixture: &str,
+) -> FilePosition {
+    let (_, cursor) = load_fixture(db, ra_fixture);
+    let (file_id, offset) = cursor.expect("fixture must contain a `<|>` cursor marker");
+    FilePosition { file_id, offset: offset.into() }
+}
+
+fn parse_fixture(ra_fixture: &str) -> Vec<FixtureEntry> {
+    let mut res = Vec::new();
+    let mut lines = ra_fixture.trim().lines().peekable();
+    while let Some(line) = lines.next() {
+        let meta = parse_meta_line(line);
+        let mut text = String::new();
+        while let Some(next) = lines.peek() {
+            if next.trim_start().starts_with("//- ") {
+                break;
+            }
+            text.push_str(lines.next().unwrap());
+            text.push('\n');
+        }
+        res.push(FixtureEntry { meta, text });
+    }
+    res
+}
+
+fn parse_meta_line(line: &str) -> FixtureMeta {
+    let line = line.trim_start();
+    assert!(line.starts_with("//- "), "fixture entry must start with `//- /path`: {:?}", line);
+    let mut words = line["//- ".len()..].split_ascii_whitespace();
+    let path = words.next().expect("fixture entry is missing a path").to_string();
+
+    let mut krate = None;
+    let mut deps = Vec::new();
+    let mut edition = Edition::Edition2018;
+    for word in words {
+        let (key, value) = word.split_at(word.find(':').expect("fixture meta must be key:value"));
+        let value = &value[1..];
+        match key {
+            "crate" => krate = Some(value.to_string()),
+            "deps" => deps = value.split(',').map(str::to_string).collect(),
+            "edition" => {
+                edition = match value {
+                    "2015" => Edition::Edition2015,
+                    "2018" => Edition::Edition2018,
+                    _ => panic!("unknown edition: {}", value),
+                }
+            }
+            _ => panic!("unknown fixture meta key: {}", key),
+        }
+    }
+
+    FixtureMeta { path, krate, deps, edition }
+}
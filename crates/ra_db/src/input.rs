@@ -0,0 +1,356 @@
+//! This module specifies the input to rust-analyzer. In some sense, this is
+//! **the** most important module, because all other fancy stuff is strictly
+//! derived from this input.
+//!
+//! Note that neither this module, nor any other part of the analyzer's core,
+//! actually cares about files on disk. All of the file system related stuff
+//! lives in `ra_vfs` / `ra_project_model`; what we have here is just plain
+//! data.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::proc_macro::ProcMacro;
+use crate::vfs_path::VfsPath;
+
+/// `FileId` is an integer which uniquely identifies a file. File paths are
+/// messy and system-dependent, so most of the code should work directly with
+/// `FileId`, without inspecting the path. A file rename keeps its `FileId`
+/// and only points it at a new `VfsPath` (see `Change::change_path`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub u32);
+
+/// Files are grouped into source roots. A source root is a directory on the
+/// file system which is watched for changes. Typically it corresponds to a
+/// Cargo package. Source roots *might* be nested: in this case, a file
+/// belongs to the nearest enclosing source root. Path to files are always
+/// relative to a source root, and the analyzer does not know the root path
+/// of the source root at all. So, a file from one source root can't refer
+/// to a file in another source root by path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceRoot {
+    pub files: FxHashMap<VfsPath, FileId>,
+}
+
+impl SourceRoot {
+    pub fn new() -> SourceRoot {
+        SourceRoot::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceRootId(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CrateId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edition {
+    Edition2018,
+    Edition2015,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub crate_id: CrateId,
+    pub name: CrateName,
+}
+
+/// A validated crate name: the identifier usable in `use` paths and
+/// `extern crate` declarations. Dashes (as used in Cargo package names) are
+/// normalized to underscores, and the result is checked to be a valid Rust
+/// identifier.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CrateName(String);
+
+impl CrateName {
+    /// Validates and normalizes a dependency name.
+    pub fn new(name: &str) -> Result<CrateName, CrateNameError> {
+        let normalized = name.replace('-', "_");
+        if is_valid_ident(&normalized) {
+            Ok(CrateName(normalized))
+        } else {
+            Err(CrateNameError(name.to_string()))
+        }
+    }
+
+    /// Unconditionally creates a crate name by replacing `-` with `_`,
+    /// without validating that the result is a legal identifier. Only
+    /// appropriate when the name is known to come from a context that
+    /// already enforces this (e.g. a Cargo package name).
+    pub fn normalize_dashes(name: &str) -> CrateName {
+        CrateName(name.replace('-', "_"))
+    }
+}
+
+impl ops::Deref for CrateName {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CrateName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateNameError(String);
+
+impl fmt::Display for CrateNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid crate name: {:?}", self.0)
+    }
+}
+
+fn is_valid_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The original, un-normalized Cargo package name, kept around for
+/// diagnostics (`CrateName` may have mangled it to make it a valid
+/// identifier).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateDisplayName(String);
+
+impl CrateDisplayName {
+    pub fn new(name: impl Into<String>) -> CrateDisplayName {
+        CrateDisplayName(name.into())
+    }
+
+    pub fn canonical_name(&self) -> CrateName {
+        CrateName::normalize_dashes(&self.0)
+    }
+}
+
+impl ops::Deref for CrateDisplayName {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CrateDisplayName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// The environment a crate's macros (`env!`, `option_env!`) see. This
+/// mirrors the Cargo-provided `CARGO_PKG_*` variables and anything set by a
+/// build script, but `ra_db` doesn't care where the values come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Env {
+    entries: FxHashMap<String, String>,
+}
+
+impl Env {
+    pub fn set(&mut self, env: &str, value: impl Into<String>) {
+        self.entries.insert(env.to_owned(), value.into());
+    }
+
+    pub fn get(&self, env: &str) -> Option<String> {
+        self.entries.get(env).cloned()
+    }
+}
+
+impl FromIterator<(String, String)> for Env {
+    fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
+        Env { entries: FxHashMap::from_iter(iter) }
+    }
+}
+
+/// The set of `#[cfg(..)]` flags active for a crate: both "atoms" like
+/// `unix` or `test`, and `key = "value"` pairs like `feature = "default"`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CfgOptions {
+    enabled: FxHashSet<String>,
+}
+
+impl CfgOptions {
+    pub fn insert_atom(&mut self, key: impl Into<String>) -> &mut CfgOptions {
+        self.enabled.insert(key.into());
+        self
+    }
+
+    pub fn insert_key_value(
+        &mut self,
+        key: impl Into<String>,
+        value: impl fmt::Display,
+    ) -> &mut CfgOptions {
+        self.enabled.insert(format!("{}={}", key.into(), value));
+        self
+    }
+
+    /// Checks whether `flag` (either a bare atom, or a `key=value` pair) is active.
+    pub fn check(&self, flag: &str) -> bool {
+        self.enabled.contains(flag)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateData {
+    pub(crate) file_id: FileId,
+    pub(crate) edition: Edition,
+    pub(crate) display_name: Option<CrateDisplayName>,
+    pub(crate) canonical_name: Option<CrateName>,
+    pub(crate) dependencies: Vec<Dependency>,
+    pub(crate) proc_macro: Vec<ProcMacro>,
+    pub(crate) env: Env,
+    pub(crate) cfg_options: CfgOptions,
+}
+
+impl CrateData {
+    fn add_dep(&mut self, name: CrateName, crate_id: CrateId) {
+        self.dependencies.push(Dependency { name, crate_id })
+    }
+}
+
+/// The crate graph stores the relationship between crates and allows to
+/// query for dependencies between them. Note that this is a graph and loops
+/// are explicitly forbidden: a crate can't (transitively) depend on itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrateGraph {
+    arena: FxHashMap<CrateId, CrateData>,
+}
+
+impl CrateGraph {
+    pub fn add_crate_root(
+        &mut self,
+        file_id: FileId,
+        edition: Edition,
+        display_name: Option<CrateDisplayName>,
+        cfg_options: CfgOptions,
+        env: Env,
+    ) -> CrateId {
+        let canonical_name = display_name.as_ref().map(CrateDisplayName::canonical_name);
+        let data = CrateData {
+            file_id,
+            edition,
+            display_name,
+            canonical_name,
+            dependencies: Vec::new(),
+            proc_macro: Vec::new(),
+            env,
+            cfg_options,
+        };
+        let crate_id = CrateId(self.arena.len() as u32);
+        let prev = self.arena.insert(crate_id, data);
+        assert!(prev.is_none());
+        crate_id
+    }
+
+    /// Attaches the set of proc-macros registered by `crate_id` to it. This
+    /// is a separate step from `add_crate_root` because the macro expanders
+    /// are typically only known once the proc-macro server has loaded the
+    /// crate's compiled dylib.
+    pub fn set_proc_macro(&mut self, crate_id: CrateId, proc_macro: Vec<ProcMacro>) {
+        self.arena.get_mut(&crate_id).unwrap().proc_macro = proc_macro;
+    }
+
+    pub fn add_dep(
+        &mut self,
+        from: CrateId,
+        name: CrateName,
+        to: CrateId,
+    ) -> Result<(), CyclicDependencyError> {
+        if self.dfs_find(from, to, &mut FxHashSet::default()) {
+            return Err(CyclicDependencyError);
+        }
+        self.arena.get_mut(&from).unwrap().add_dep(name, to);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+
+    pub fn crate_root(&self, crate_id: CrateId) -> FileId {
+        self.arena[&crate_id].file_id
+    }
+
+    pub fn crate_id_for_crate_root(&self, file_id: FileId) -> Option<CrateId> {
+        let (&crate_id, _) = self.arena.iter().find(|(_, data)| data.file_id == file_id)?;
+        Some(crate_id)
+    }
+
+    /// Like `crate_id_for_crate_root`, but resolves by canonical crate name
+    /// instead of root file, for cases (e.g. `extern crate foo;`) where only
+    /// the name is known.
+    pub fn crate_id_for_name(&self, name: &CrateName) -> Option<CrateId> {
+        let (&crate_id, _) =
+            self.arena.iter().find(|(_, data)| data.canonical_name.as_ref() == Some(name))?;
+        Some(crate_id)
+    }
+
+    pub fn display_name(&self, crate_id: CrateId) -> Option<&CrateDisplayName> {
+        self.arena[&crate_id].display_name.as_ref()
+    }
+
+    pub fn crate_name(&self, crate_id: CrateId) -> Option<&CrateName> {
+        self.arena[&crate_id].canonical_name.as_ref()
+    }
+
+    pub fn edition(&self, crate_id: CrateId) -> Edition {
+        self.arena[&crate_id].edition
+    }
+
+    pub fn dependencies<'a>(
+        &'a self,
+        crate_id: CrateId,
+    ) -> impl Iterator<Item = &'a Dependency> + 'a {
+        self.arena[&crate_id].dependencies.iter()
+    }
+
+    pub fn proc_macro(&self, crate_id: CrateId) -> &[ProcMacro] {
+        &self.arena[&crate_id].proc_macro
+    }
+
+    pub fn env(&self, crate_id: CrateId) -> &Env {
+        &self.arena[&crate_id].env
+    }
+
+    pub fn cfg_options(&self, crate_id: CrateId) -> &CfgOptions {
+        &self.arena[&crate_id].cfg_options
+    }
+
+    /// Returns ids of all crates in this crate graph.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = CrateId> + 'a {
+        self.arena.keys().copied()
+    }
+
+    fn dfs_find(&self, target: CrateId, from: CrateId, visited: &mut FxHashSet<CrateId>) -> bool {
+        if !visited.insert(from) {
+            return false;
+        }
+        if target == from {
+            return true;
+        }
+        for dep in self.dependencies(from) {
+            if self.dfs_find(target, dep.crate_id, visited) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[derive(Debug)]
+pub struct CyclicDependencyError;
+
+impl fmt::Display for CyclicDependencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cyclic dependency between crates")
+    }
+}
+
@@ -1,19 +1,26 @@
 //! ra_db defines basic database traits. The concrete DB is defined by ra_ide_api.
 mod cancellation;
 mod input;
+mod change;
+mod proc_macro;
+mod vfs_path;
+pub mod fixture;
 
 use std::{panic, sync::Arc};
 
 use ra_syntax::{TextUnit, TextRange, SourceFile, Parse};
-use relative_path::RelativePathBuf;
 use ra_prof::profile;
 
 pub use ::salsa as salsa;
 pub use crate::{
     cancellation::Canceled,
     input::{
-        FileId, CrateId, SourceRoot, SourceRootId, CrateGraph, Dependency, Edition,
+        FileId, CrateId, SourceRoot, SourceRootId, CrateGraph, Dependency, Edition, Env,
+        CfgOptions, CrateName, CrateNameError, CrateDisplayName,
     },
+    change::Change,
+    proc_macro::{ProcMacro, ProcMacroId, ProcMacroKind, ProcMacroExpander, ProcMacroExpansionError},
+    vfs_path::{VfsPath, AnchoredPath, AnchoredPathBuf},
 };
 
 pub trait CheckCanceled {
@@ -79,7 +86,7 @@ pub trait SourceDatabase: CheckCanceled + std::fmt::Debug {
     fn parse(&self, file_id: FileId) -> Parse;
     /// Path to a file, relative to the root of its source root.
     #[salsa::input]
-    fn file_relative_path(&self, file_id: FileId) -> RelativePathBuf;
+    fn file_relative_path(&self, file_id: FileId) -> VfsPath;
     /// Source root of the file.
     #[salsa::input]
     fn file_source_root(&self, file_id: FileId) -> SourceRootId;
@@ -90,6 +97,15 @@ pub trait SourceDatabase: CheckCanceled + std::fmt::Debug {
     /// The crate graph.
     #[salsa::input]
     fn crate_graph(&self) -> Arc<CrateGraph>;
+    /// Proc-macros registered by a crate, ready to be invoked by name.
+    #[salsa::invoke(proc_macros_query)]
+    fn proc_macros(&self, krate: CrateId) -> Arc<Vec<ProcMacro>>;
+    /// Resolves a path anchored at a file (e.g. the target of `mod foo;` or
+    /// `include!("foo.rs")`) to the `FileId` it points at, if any. Not
+    /// memoized, since `AnchoredPath` borrows and can't be a salsa key.
+    #[salsa::transparent]
+    #[salsa::invoke(resolve_path_query)]
+    fn resolve_path(&self, path: AnchoredPath<'_>) -> Option<FileId>;
 }
 
 fn source_root_crates(db: &impl SourceDatabase, id: SourceRootId) -> Arc<Vec<CrateId>> {
@@ -105,3 +121,22 @@ fn parse_query(db: &impl SourceDatabase, file_id: FileId) -> Parse {
     let text = db.file_text(file_id);
     SourceFile::parse(&*text)
 }
+
+fn proc_macros_query(db: &impl SourceDatabase, krate: CrateId) -> Arc<Vec<ProcMacro>> {
+    let crate_graph = db.crate_graph();
+    Arc::new(crate_graph.proc_macro(krate).to_vec())
+}
+
+fn resolve_path_query(db: &impl SourceDatabase, path: AnchoredPath<'_>) -> Option<FileId> {
+    let anchor_path = db.file_relative_path(path.anchor);
+    let target_path = anchor_path.join(path.path)?;
+    let source_root_id = db.file_source_root(path.anchor);
+    let source_root = db.source_root(source_root_id);
+    source_root.files.get(&target_path).copied()
+}
+
+/// Looks up the value of environment variable `key` for `krate`, as seen by
+/// `env!`/`option_env!` expansion during macro resolution.
+pub fn crate_env_var(db: &impl SourceDatabase, krate: CrateId, key: &str) -> Option<String> {
+    db.crate_graph().env(krate).get(key)
+}
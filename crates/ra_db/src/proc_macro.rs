@@ -0,0 +1,59 @@
+//! Types for driving procedural macro expansion from `ra_db`'s point of
+//! view. The actual expansion happens out-of-process (in a proc-macro
+//! server), so what we store here is just an opaque, boxed expander plus
+//! enough metadata to look the right one up by name and call convention.
+
+use std::fmt;
+use std::sync::Arc;
+
+use ra_tt::Subtree;
+
+/// Points at a single proc-macro within a crate's registered set, by its
+/// position in `CrateData::proc_macro`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProcMacroId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcMacroKind {
+    CustomDerive,
+    FuncLike,
+    Attr,
+}
+
+/// An opaque handle to a concrete proc-macro expander, implemented by
+/// whatever drives the actual expansion (e.g. a sandboxed proc-macro
+/// server). `ra_db` only needs to be able to call it and get tokens back.
+pub trait ProcMacroExpander: fmt::Debug + Send + Sync {
+    fn expand(
+        &self,
+        subtree: &Subtree,
+        attrs: Option<&Subtree>,
+    ) -> Result<Subtree, ProcMacroExpansionError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcMacroExpansionError(pub String);
+
+impl fmt::Display for ProcMacroExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "proc-macro expansion failed: {}", self.0)
+    }
+}
+
+/// A single procedural macro registered by a crate: its name (as it appears
+/// in `use`/derive position), its kind, and the expander that runs it.
+#[derive(Debug, Clone)]
+pub struct ProcMacro {
+    pub name: String,
+    pub kind: ProcMacroKind,
+    pub expander: Arc<dyn ProcMacroExpander>,
+}
+
+impl Eq for ProcMacro {}
+impl PartialEq for ProcMacro {
+    fn eq(&self, other: &ProcMacro) -> bool {
+        self.name == other.name
+            && self.kind == other.kind
+            && Arc::ptr_eq(&self.expander, &other.expander)
+    }
+}
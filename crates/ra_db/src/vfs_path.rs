@@ -0,0 +1,73 @@
+//! `ra_db` identifies files by a path relative to the root of whatever
+//! `SourceRoot` owns them. This module wraps that identity in `VfsPath`
+//! (instead of a bare `RelativePathBuf`) so that call sites stop assuming a
+//! file's path is forever fixed: a rename is just `Change::change_path`
+//! pointing the same `FileId` at a new `VfsPath`.
+//!
+//! It also introduces `AnchoredPath`, for references that are resolved
+//! relative to another file rather than to the source root — the case for
+//! `mod foo;` and `include!("foo.rs")`.
+
+use std::fmt;
+
+use relative_path::{RelativePath, RelativePathBuf};
+
+use crate::FileId;
+
+/// The identity of a file, relative to the root of its source root.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VfsPath(RelativePathBuf);
+
+impl VfsPath {
+    pub fn as_path(&self) -> &RelativePath {
+        &self.0
+    }
+
+    /// Resolves `path` (e.g. the argument of `mod foo;` or `include!`)
+    /// relative to `self`, treating `self` as the path of the file doing
+    /// the including.
+    pub fn join(&self, path: &str) -> Option<VfsPath> {
+        let parent = self.0.parent().unwrap_or_else(|| RelativePath::new(""));
+        Some(VfsPath(parent.join(path)))
+    }
+}
+
+impl From<RelativePathBuf> for VfsPath {
+    fn from(path: RelativePathBuf) -> VfsPath {
+        VfsPath(path)
+    }
+}
+
+impl fmt::Display for VfsPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// A path relative to a file, rather than to a source root: the natural
+/// shape of a `mod foo;` or `include!("foo.rs")` reference, which is
+/// resolved relative to the file that contains it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AnchoredPath<'a> {
+    pub anchor: FileId,
+    pub path: &'a str,
+}
+
+impl<'a> AnchoredPath<'a> {
+    pub fn new(anchor: FileId, path: &'a str) -> AnchoredPath<'a> {
+        AnchoredPath { anchor, path }
+    }
+}
+
+/// Owned counterpart of `AnchoredPath`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AnchoredPathBuf {
+    pub anchor: FileId,
+    pub path: String,
+}
+
+impl<'a> From<AnchoredPath<'a>> for AnchoredPathBuf {
+    fn from(path: AnchoredPath<'a>) -> AnchoredPathBuf {
+        AnchoredPathBuf { anchor: path.anchor, path: path.path.to_string() }
+    }
+}